@@ -0,0 +1,76 @@
+use rand::Rng;
+use std::sync::Mutex;
+
+/// How much weight a new latency sample gets in an endpoint's running
+/// average; lower reacts slower but is less noisy.
+const EWMA_ALPHA: f64 = 0.2;
+
+struct Endpoint {
+    url: String,
+    ewma_us: Mutex<f64>,
+}
+
+impl Endpoint {
+    fn load(&self) -> f64 {
+        *self.ewma_us.lock().unwrap()
+    }
+
+    fn record(&self, latency_us: u64) {
+        let mut ewma = self.ewma_us.lock().unwrap();
+        *ewma = if *ewma == 0.0 {
+            latency_us as f64
+        } else {
+            EWMA_ALPHA * latency_us as f64 + (1.0 - EWMA_ALPHA) * *ewma
+        };
+    }
+}
+
+/// Distributes requests across a pool of backend URLs using power-of-two-choices:
+/// for each request, sample two endpoints at random and send to whichever has
+/// the lower recent-latency EWMA. Degenerates to always picking index 0 when
+/// there's only a single endpoint.
+pub struct Balancer {
+    endpoints: Vec<Endpoint>,
+}
+
+impl Balancer {
+    pub fn new(urls: Vec<String>) -> Self {
+        let endpoints = urls
+            .into_iter()
+            .map(|url| Endpoint { url, ewma_us: Mutex::new(0.0) })
+            .collect();
+        Balancer { endpoints }
+    }
+
+    pub fn len(&self) -> usize {
+        self.endpoints.len()
+    }
+
+    pub fn url(&self, idx: usize) -> &str {
+        &self.endpoints[idx].url
+    }
+
+    /// Pick an endpoint index to send the next request to.
+    pub fn pick(&self) -> usize {
+        if self.endpoints.len() <= 1 {
+            return 0;
+        }
+
+        let mut rng = rand::thread_rng();
+        let a = rng.gen_range(0..self.endpoints.len());
+        let mut b = rng.gen_range(0..self.endpoints.len() - 1);
+        if b >= a {
+            b += 1;
+        }
+
+        if self.endpoints[a].load() <= self.endpoints[b].load() {
+            a
+        } else {
+            b
+        }
+    }
+
+    pub fn record(&self, idx: usize, latency_us: u64) {
+        self.endpoints[idx].record(latency_us);
+    }
+}
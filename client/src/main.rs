@@ -1,17 +1,30 @@
-use clap::Parser;
+mod balancer;
+mod report;
+mod script;
+mod tls;
+mod ws;
+
+use balancer::Balancer;
+use clap::{Parser, Subcommand};
 use futures::stream::{self, StreamExt};
 use hdrhistogram::Histogram;
+use report::{OutputFormat, ResponseStatistic, Sample};
+use script::Script;
+use tls::TlsArgs;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use tokio::sync::mpsc;
+use ws::WsArgs;
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 struct Args {
-    /// Target URL
+    /// Target URL. Repeat to load-balance across a pool of backends.
     #[arg(short, long, default_value = "http://127.0.0.1:3000/")]
-    url: String,
+    url: Vec<String>,
 
     /// Number of concurrent requests
     #[arg(short, long, default_value_t = 10)]
@@ -20,36 +33,168 @@ struct Args {
     /// Total number of requests
     #[arg(short, long, default_value_t = 100)]
     requests: usize,
+
+    /// Report output format
+    #[arg(long, value_enum, default_value = "text")]
+    output: OutputFormat,
+
+    /// Write the report to this file in addition to stdout
+    #[arg(long)]
+    report_file: Option<PathBuf>,
+
+    /// Lua script exposing a `request(i)` function for per-iteration requests
+    #[arg(long)]
+    script: Option<PathBuf>,
+
+    /// Issue requests on a fixed schedule at this many requests/second
+    /// instead of firing as fast as concurrency allows
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+    rate: Option<u64>,
+
+    /// Discard samples scheduled within this many seconds of the start
+    #[arg(long, default_value_t = 0)]
+    warm_up: u64,
+
+    /// Print interim percentile reports roughly this often, in seconds
+    #[arg(long)]
+    sample_rate: Option<u64>,
+
+    #[command(flatten)]
+    tls: TlsArgs,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Drive a WebSocket endpoint instead of plain HTTP
+    Ws(WsArgs),
 }
 
 #[tokio::main]
 async fn main() {
     let args = Args::parse();
-    let client = reqwest::Client::new();
+
+    if let Some(Command::Ws(ws_args)) = args.command {
+        ws::run(ws_args).await;
+        return;
+    }
+
+    let client = tls::build_client(&args.tls);
     let success_count = Arc::new(AtomicUsize::new(0));
     let failure_count = Arc::new(AtomicUsize::new(0));
+    let script = args.script.as_deref().map(|path| {
+        Script::load(path).unwrap_or_else(|e| panic!("failed to load script {}: {}", path.display(), e))
+    });
+    let balancer = Arc::new(Balancer::new(args.url.clone()));
 
-    println!("Starting load test against {}", args.url);
+    if balancer.len() > 1 {
+        println!("Starting load test against {} backends (P2C): {}", balancer.len(), args.url.join(", "));
+    } else {
+        println!("Starting load test against {}", args.url[0]);
+    }
     println!("Concurrency: {}", args.concurrency);
     println!("Total requests: {}", args.requests);
+    let expected_interval_us = args.rate.map(|rate| 1_000_000 / rate);
+    if let Some(interval) = expected_interval_us {
+        println!("Open-model pacing: {} req/s ({} µs interval)", args.rate.unwrap(), interval);
+    }
 
-    let (tx, mut rx) = mpsc::channel(args.requests);
+    let (tx, rx) = mpsc::channel::<Sample>(args.requests);
     let start_time = Instant::now();
+    let warm_up = Duration::from_secs(args.warm_up);
+    let sample_rate = args.sample_rate;
+
+    let endpoint_count = balancer.len();
+    let consumer = tokio::spawn(async move {
+        let mut rx = rx;
+        let mut hist = Histogram::<u64>::new(3).unwrap();
+        let mut status_counts: BTreeMap<u16, u64> = BTreeMap::new();
+        let mut per_endpoint_hist: Vec<Histogram<u64>> =
+            (0..endpoint_count).map(|_| Histogram::new(3).unwrap()).collect();
+        let mut count_by_instance: Vec<u64> = vec![0; endpoint_count];
+        let mut last_report = Instant::now();
+        while let Some(sample) = rx.recv().await {
+            match sample.expected_interval_us {
+                Some(interval) => {
+                    hist.record_correct(sample.duration_us, interval).unwrap();
+                    per_endpoint_hist[sample.endpoint].record_correct(sample.duration_us, interval).unwrap();
+                }
+                None => {
+                    hist.record(sample.duration_us).unwrap();
+                    per_endpoint_hist[sample.endpoint].record(sample.duration_us).unwrap();
+                }
+            }
+            *status_counts.entry(sample.status).or_insert(0) += 1;
+            count_by_instance[sample.endpoint] += 1;
+
+            if let Some(sample_rate) = sample_rate {
+                if last_report.elapsed() >= Duration::from_secs(sample_rate) {
+                    println!(
+                        "[interim] P50: {} µs  P90: {} µs  P99: {} µs",
+                        hist.value_at_percentile(50.0),
+                        hist.value_at_percentile(90.0),
+                        hist.value_at_percentile(99.0)
+                    );
+                    last_report = Instant::now();
+                }
+            }
+        }
+        (hist, status_counts, per_endpoint_hist, count_by_instance)
+    });
 
     let requests = stream::iter(0..args.requests);
     requests
-        .for_each_concurrent(args.concurrency, |_| {
+        .for_each_concurrent(args.concurrency, |i| {
             let client = client.clone();
-            let url = args.url.clone();
+            let balancer = balancer.clone();
             let success_count = success_count.clone();
             let failure_count = failure_count.clone();
             let tx = tx.clone();
+            let script = script.as_ref().map(Script::clone_handle);
             async move {
-                let start = Instant::now();
-                match client.get(&url).send().await {
+                let scheduled = match expected_interval_us {
+                    Some(interval) => {
+                        let offset = Duration::from_micros(interval * i as u64);
+                        let scheduled = start_time + offset;
+                        tokio::time::sleep_until(tokio::time::Instant::from_std(scheduled)).await;
+                        scheduled
+                    }
+                    None => Instant::now(),
+                };
+
+                // Warm-up requests are still sent so the system under test
+                // ramps up under real load, but they're excluded from every
+                // reported metric (histogram, status counts, success/failure
+                // totals) so the report reflects only steady-state behavior.
+                let warming_up = scheduled.duration_since(start_time) < warm_up;
+
+                let endpoint = balancer.pick();
+                let request = match &script {
+                    Some(script) => match script.request(i).await {
+                        Ok(spec) => script::build_request(&client, &spec),
+                        Err(_) => {
+                            if !warming_up {
+                                failure_count.fetch_add(1, Ordering::Relaxed);
+                            }
+                            return;
+                        }
+                    },
+                    None => client.get(balancer.url(endpoint)),
+                };
+
+                match request.send().await {
                     Ok(resp) => {
-                        let duration = start.elapsed().as_micros() as u64;
-                        let _ = tx.send(duration).await;
+                        let duration_us = scheduled.elapsed().as_micros() as u64;
+                        let status = resp.status().as_u16();
+                        balancer.record(endpoint, duration_us);
+                        if warming_up {
+                            return;
+                        }
+                        let _ = tx
+                            .send(Sample { duration_us, status, expected_interval_us, endpoint })
+                            .await;
                         if resp.status().is_success() {
                             success_count.fetch_add(1, Ordering::Relaxed);
                         } else {
@@ -57,22 +202,36 @@ async fn main() {
                         }
                     }
                     Err(_) => {
-                        failure_count.fetch_add(1, Ordering::Relaxed);
+                        if !warming_up {
+                            failure_count.fetch_add(1, Ordering::Relaxed);
+                        }
                     }
                 }
             }
         })
         .await;
 
-    // Drop the original sender so the receiver knows when to stop
+    // Drop the original sender so the consumer task knows when to stop
     drop(tx);
+    let (hist, status_counts, per_endpoint_hist, count_by_instance) = consumer.await.unwrap();
+
+    let duration = start_time.elapsed();
 
-    let mut hist = Histogram::<u64>::new(3).unwrap();
-    while let Some(duration) = rx.recv().await {
-        hist.record(duration).unwrap();
+    let urls: Vec<String> = (0..balancer.len()).map(|idx| balancer.url(idx).to_string()).collect();
+
+    if matches!(args.output, OutputFormat::Json | OutputFormat::Csv) {
+        let stats = ResponseStatistic::from_samples(
+            &hist,
+            status_counts,
+            duration.as_secs_f64(),
+            &per_endpoint_hist,
+            &count_by_instance,
+            &urls,
+        );
+        stats.emit(args.output, args.report_file.as_deref());
+        return;
     }
 
-    let duration = start_time.elapsed();
     let success = success_count.load(Ordering::Relaxed);
     let failure = failure_count.load(Ordering::Relaxed);
 
@@ -90,6 +249,25 @@ async fn main() {
     println!("P99:  {}", hist.value_at_percentile(99.0));
     println!("Max:  {}", hist.max());
 
+    println!("\nStatus code breakdown:");
+    for (status, count) in &status_counts {
+        println!("{:5}: {}", status, count);
+    }
+
+    if balancer.len() > 1 {
+        println!("\nPer-endpoint breakdown:");
+        for (idx, endpoint_hist) in per_endpoint_hist.iter().enumerate() {
+            println!(
+                "{:30} count: {:6}  P50: {:6} µs  P90: {:6} µs  P99: {:6} µs",
+                balancer.url(idx),
+                count_by_instance[idx],
+                endpoint_hist.value_at_percentile(50.0),
+                endpoint_hist.value_at_percentile(90.0),
+                endpoint_hist.value_at_percentile(99.0)
+            );
+        }
+    }
+
     println!("\nLatency Distribution:");
     // Simple ASCII visualization
     let max_count = hist.iter_linear(1000).map(|iter| iter.count_since_last_iteration()).max().unwrap_or(1);
@@ -107,4 +285,16 @@ async fn main() {
             );
         }
     }
+
+    if let Some(path) = &args.report_file {
+        let stats = ResponseStatistic::from_samples(
+            &hist,
+            status_counts,
+            duration.as_secs_f64(),
+            &per_endpoint_hist,
+            &count_by_instance,
+            &urls,
+        );
+        stats.write_file(OutputFormat::Text, path);
+    }
 }
@@ -0,0 +1,186 @@
+use clap::ValueEnum;
+use hdrhistogram::Histogram;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+
+/// Output format for the final report.
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum OutputFormat {
+    Text,
+    Json,
+    Csv,
+}
+
+/// A single sample recorded for one completed request: how long it took,
+/// the HTTP status code it came back with, and which backend served it.
+///
+/// `expected_interval_us` is set in open-model (`--rate`) mode: the sample
+/// must be recorded with `Histogram::record_correct` against that interval
+/// to avoid coordinated omission, instead of a plain `record`.
+pub struct Sample {
+    pub duration_us: u64,
+    pub status: u16,
+    pub expected_interval_us: Option<u64>,
+    pub endpoint: usize,
+}
+
+/// Per-backend latency breakdown, present when the load test targeted more
+/// than one `--url` and P2C balanced across them.
+#[derive(Serialize)]
+pub struct EndpointStatistic {
+    pub url: String,
+    pub count: u64,
+    pub p50_us: u64,
+    pub p90_us: u64,
+    pub p99_us: u64,
+}
+
+/// Machine-readable summary of a load test run, suitable for CI regression
+/// tracking.
+#[derive(Serialize)]
+pub struct ResponseStatistic {
+    pub total: u64,
+    pub successful: u64,
+    pub failed: u64,
+    pub requests_per_second: f64,
+    pub p50_us: u64,
+    pub p90_us: u64,
+    pub p99_us: u64,
+    pub p999_us: u64,
+    pub max_us: u64,
+    pub status_counts: BTreeMap<u16, u64>,
+    pub per_endpoint: Vec<EndpointStatistic>,
+}
+
+impl ResponseStatistic {
+    pub fn from_samples(
+        hist: &Histogram<u64>,
+        status_counts: BTreeMap<u16, u64>,
+        elapsed_secs: f64,
+        per_endpoint_hist: &[Histogram<u64>],
+        count_by_instance: &[u64],
+        urls: &[String],
+    ) -> Self {
+        let failed = status_counts
+            .iter()
+            .filter(|(status, _)| !(200..300).contains(*status))
+            .map(|(_, count)| *count)
+            .sum();
+        let total: u64 = status_counts.values().sum();
+
+        let per_endpoint = if urls.len() > 1 {
+            urls.iter()
+                .zip(per_endpoint_hist)
+                .zip(count_by_instance)
+                .map(|((url, hist), count)| EndpointStatistic {
+                    url: url.clone(),
+                    count: *count,
+                    p50_us: hist.value_at_percentile(50.0),
+                    p90_us: hist.value_at_percentile(90.0),
+                    p99_us: hist.value_at_percentile(99.0),
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        ResponseStatistic {
+            total,
+            successful: total - failed,
+            failed,
+            requests_per_second: total as f64 / elapsed_secs,
+            p50_us: hist.value_at_percentile(50.0),
+            p90_us: hist.value_at_percentile(90.0),
+            p99_us: hist.value_at_percentile(99.0),
+            p999_us: hist.value_at_percentile(99.9),
+            max_us: hist.max(),
+            status_counts,
+            per_endpoint,
+        }
+    }
+
+    fn to_text(&self) -> String {
+        let mut out = String::new();
+        out.push_str(&format!("Total requests: {}\n", self.total));
+        out.push_str(&format!("Successful requests: {}\n", self.successful));
+        out.push_str(&format!("Failed requests: {}\n", self.failed));
+        out.push_str(&format!("Requests per second: {:.2}\n", self.requests_per_second));
+        out.push_str("\nLatency Percentiles (µs):\n");
+        out.push_str(&format!("P50:   {}\n", self.p50_us));
+        out.push_str(&format!("P90:   {}\n", self.p90_us));
+        out.push_str(&format!("P99:   {}\n", self.p99_us));
+        out.push_str(&format!("P999:  {}\n", self.p999_us));
+        out.push_str(&format!("Max:   {}\n", self.max_us));
+        out.push_str("\nStatus code breakdown:\n");
+        for (status, count) in &self.status_counts {
+            out.push_str(&format!("{:5}: {}\n", status, count));
+        }
+        if !self.per_endpoint.is_empty() {
+            out.push_str("\nPer-endpoint breakdown:\n");
+            for endpoint in &self.per_endpoint {
+                out.push_str(&format!(
+                    "{:30} count: {:6}  P50: {:6} µs  P90: {:6} µs  P99: {:6} µs\n",
+                    endpoint.url, endpoint.count, endpoint.p50_us, endpoint.p90_us, endpoint.p99_us
+                ));
+            }
+        }
+        out
+    }
+
+    fn to_csv(&self) -> String {
+        let mut out = String::new();
+        out.push_str("metric,value\n");
+        out.push_str(&format!("total,{}\n", self.total));
+        out.push_str(&format!("successful,{}\n", self.successful));
+        out.push_str(&format!("failed,{}\n", self.failed));
+        out.push_str(&format!("requests_per_second,{:.2}\n", self.requests_per_second));
+        out.push_str(&format!("p50_us,{}\n", self.p50_us));
+        out.push_str(&format!("p90_us,{}\n", self.p90_us));
+        out.push_str(&format!("p99_us,{}\n", self.p99_us));
+        out.push_str(&format!("p999_us,{}\n", self.p999_us));
+        out.push_str(&format!("max_us,{}\n", self.max_us));
+        for (status, count) in &self.status_counts {
+            out.push_str(&format!("status_{},{}\n", status, count));
+        }
+        for endpoint in &self.per_endpoint {
+            out.push_str(&format!(
+                "endpoint_{}_count,{}\nendpoint_{}_p50_us,{}\nendpoint_{}_p90_us,{}\nendpoint_{}_p99_us,{}\n",
+                endpoint.url, endpoint.count, endpoint.url, endpoint.p50_us, endpoint.url, endpoint.p90_us, endpoint.url, endpoint.p99_us
+            ));
+        }
+        out
+    }
+
+    fn render(&self, format: OutputFormat) -> String {
+        match format {
+            OutputFormat::Text => self.to_text(),
+            OutputFormat::Json => serde_json::to_string_pretty(self).unwrap(),
+            OutputFormat::Csv => self.to_csv(),
+        }
+    }
+
+    /// Render this report in `format` and print it to stdout, additionally
+    /// writing it to `report_file` if one was given.
+    pub fn emit(&self, format: OutputFormat, report_file: Option<&Path>) {
+        let rendered = self.render(format);
+        println!("{}", rendered);
+        if let Some(path) = report_file {
+            self.write_file(format, path);
+        }
+    }
+
+    /// Render this report in `format` and write it to `path` without also
+    /// printing to stdout. Used when a human-readable summary has already
+    /// been printed and `--report-file` needs the report on disk as well.
+    /// Its counts come from the status-code breakdown, so requests that got
+    /// no response are not in them, unlike the printed failure count.
+    pub fn write_file(&self, format: OutputFormat, path: &Path) {
+        let rendered = self.render(format);
+        if let Ok(mut file) = File::create(path) {
+            let _ = file.write_all(rendered.as_bytes());
+        }
+    }
+}
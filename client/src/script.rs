@@ -0,0 +1,76 @@
+use mlua::{Lua, Table};
+use reqwest::{Client, RequestBuilder};
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// One request's worth of parameters, produced by the Lua `request(i)` hook.
+pub struct RequestSpec {
+    pub method: String,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<String>,
+}
+
+/// Wraps a loaded Lua script exposing a `request(i)` function. Lua state
+/// isn't safely callable from multiple tasks at once, so calls are
+/// serialized behind a mutex; this is cheap relative to the network I/O
+/// each call feeds into.
+pub struct Script {
+    lua: Arc<Mutex<Lua>>,
+}
+
+impl Script {
+    pub fn load(path: &Path) -> mlua::Result<Self> {
+        let source = fs::read_to_string(path)
+            .unwrap_or_else(|e| panic!("failed to read script {}: {}", path.display(), e));
+        let lua = Lua::new();
+        lua.load(&source).exec()?;
+        Ok(Script { lua: Arc::new(Mutex::new(lua)) })
+    }
+
+    pub fn clone_handle(&self) -> Self {
+        Script { lua: self.lua.clone() }
+    }
+
+    /// Call the script's `request(i)` function for iteration `i`.
+    pub async fn request(&self, i: usize) -> mlua::Result<RequestSpec> {
+        let lua = self.lua.lock().await;
+        let request_fn: mlua::Function = lua.globals().get("request")?;
+        let table: Table = request_fn.call(i)?;
+
+        let method: String = table.get("method").unwrap_or_else(|_| "GET".to_string());
+        let url: String = table.get("url")?;
+        let body: Option<String> = table.get("body").ok();
+
+        let mut headers = Vec::new();
+        if let Ok(header_table) = table.get::<_, Table>("headers") {
+            for pair in header_table.pairs::<String, String>() {
+                let (key, value) = pair?;
+                headers.push((key, value));
+            }
+        }
+
+        Ok(RequestSpec { method, url, headers, body })
+    }
+}
+
+/// Build a `reqwest::RequestBuilder` from a script-provided spec.
+pub fn build_request(client: &Client, spec: &RequestSpec) -> RequestBuilder {
+    let method = spec
+        .method
+        .parse::<reqwest::Method>()
+        .unwrap_or(reqwest::Method::GET);
+    let mut builder = client.request(method, &spec.url);
+
+    for (key, value) in &spec.headers {
+        builder = builder.header(key, value);
+    }
+
+    if let Some(body) = &spec.body {
+        builder = builder.body(body.clone());
+    }
+
+    builder
+}
@@ -0,0 +1,73 @@
+use clap::Args as ClapArgs;
+use reqwest::{Certificate, Client, Identity};
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// TLS and connection-pool tuning flags for the HTTP client, grouped for
+/// `#[command(flatten)]` into the top-level `Args`.
+#[derive(ClapArgs, Debug)]
+pub struct TlsArgs {
+    /// Force HTTP/2 via prior knowledge (no ALPN negotiation)
+    #[arg(long)]
+    pub http2: bool,
+
+    /// Skip TLS certificate verification (self-signed test servers only)
+    #[arg(long)]
+    pub insecure: bool,
+
+    /// Trust this CA certificate (PEM) in addition to the bundled webpki roots
+    #[arg(long)]
+    pub ca_cert: Option<PathBuf>,
+
+    /// Client certificate (PEM) for mTLS; requires --client-key
+    #[arg(long, requires = "client_key")]
+    pub client_cert: Option<PathBuf>,
+
+    /// Client private key (PEM) for mTLS; requires --client-cert
+    #[arg(long, requires = "client_cert")]
+    pub client_key: Option<PathBuf>,
+
+    /// How long an idle pooled connection is kept alive, in seconds
+    #[arg(long)]
+    pub pool_idle_timeout: Option<u64>,
+
+    /// Maximum idle connections kept per host
+    #[arg(long)]
+    pub pool_max_idle_per_host: Option<usize>,
+}
+
+/// Build the `reqwest::Client` the load tester sends requests through,
+/// applying whichever TLS and pool flags were passed on the command line.
+pub fn build_client(args: &TlsArgs) -> Client {
+    let mut builder = Client::builder().use_rustls_tls();
+
+    if args.http2 {
+        builder = builder.http2_prior_knowledge();
+    }
+    if args.insecure {
+        builder = builder.danger_accept_invalid_certs(true);
+    }
+    if let Some(path) = &args.ca_cert {
+        let pem = fs::read(path).unwrap_or_else(|e| panic!("failed to read CA cert {}: {}", path.display(), e));
+        let cert = Certificate::from_pem(&pem).expect("invalid CA certificate");
+        builder = builder.add_root_certificate(cert);
+    }
+    if let (Some(cert_path), Some(key_path)) = (&args.client_cert, &args.client_key) {
+        let mut pem = fs::read(cert_path)
+            .unwrap_or_else(|e| panic!("failed to read client cert {}: {}", cert_path.display(), e));
+        let mut key_pem = fs::read(key_path)
+            .unwrap_or_else(|e| panic!("failed to read client key {}: {}", key_path.display(), e));
+        pem.append(&mut key_pem);
+        let identity = Identity::from_pem(&pem).expect("invalid client cert/key pair");
+        builder = builder.identity(identity);
+    }
+    if let Some(secs) = args.pool_idle_timeout {
+        builder = builder.pool_idle_timeout(Duration::from_secs(secs));
+    }
+    if let Some(n) = args.pool_max_idle_per_host {
+        builder = builder.pool_max_idle_per_host(n);
+    }
+
+    builder.build().expect("failed to build HTTP client")
+}
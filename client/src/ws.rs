@@ -0,0 +1,148 @@
+use clap::Args as ClapArgs;
+use futures::stream::{self, StreamExt};
+use futures::{SinkExt, TryStreamExt};
+use hdrhistogram::Histogram;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::mpsc;
+use tokio_tungstenite::connect_async;
+use tokio_tungstenite::tungstenite::Message;
+
+/// Arguments specific to the `ws` subcommand.
+#[derive(ClapArgs, Debug)]
+pub struct WsArgs {
+    /// Target WebSocket URL (ws:// or wss://)
+    #[arg(short, long, default_value = "ws://127.0.0.1:3000/ws")]
+    pub url: String,
+
+    /// Number of concurrent connections
+    #[arg(short, long, default_value_t = 10)]
+    pub concurrency: usize,
+
+    /// Total number of messages to send (spread across connections)
+    #[arg(short, long, default_value_t = 100)]
+    pub requests: usize,
+
+    /// Send binary frames instead of text frames
+    #[arg(long)]
+    pub binary: bool,
+
+    /// Payload size in KB
+    #[arg(long, default_value_t = 1)]
+    pub size: usize,
+
+    /// Reconnect a connection once it has sent this many KB
+    #[arg(long, default_value_t = 1024)]
+    pub max_payload: usize,
+}
+
+/// Run the WebSocket load test, recording round-trip latency into a shared histogram.
+pub async fn run(args: WsArgs) {
+    println!("Starting WebSocket load test against {}", args.url);
+    println!("Concurrency: {}", args.concurrency);
+    println!("Total messages: {}", args.requests);
+
+    let success_count = Arc::new(AtomicUsize::new(0));
+    let failure_count = Arc::new(AtomicUsize::new(0));
+    let payload = vec![b'x'; args.size * 1024];
+
+    let (tx, mut rx) = mpsc::channel(args.requests);
+    let start_time = Instant::now();
+
+    let base_per_conn = args.requests / args.concurrency;
+    let remainder = args.requests % args.concurrency;
+
+    stream::iter(0..args.concurrency)
+        .for_each_concurrent(args.concurrency, |conn| {
+            // The first `remainder` connections send one extra message, so
+            // exactly `requests` messages are sent in total.
+            let messages_per_conn = base_per_conn + usize::from(conn < remainder);
+            let url = args.url.clone();
+            let payload = payload.clone();
+            let binary = args.binary;
+            let max_payload = args.max_payload * 1024;
+            let success_count = success_count.clone();
+            let failure_count = failure_count.clone();
+            let tx = tx.clone();
+            async move {
+                if messages_per_conn == 0 {
+                    return;
+                }
+                let mut bytes_sent = 0usize;
+                let mut socket = match connect_async(&url).await {
+                    Ok((socket, _)) => socket,
+                    Err(_) => {
+                        failure_count.fetch_add(messages_per_conn, Ordering::Relaxed);
+                        return;
+                    }
+                };
+
+                for _ in 0..messages_per_conn {
+                    if bytes_sent >= max_payload {
+                        socket = match connect_async(&url).await {
+                            Ok((socket, _)) => socket,
+                            Err(_) => {
+                                failure_count.fetch_add(1, Ordering::Relaxed);
+                                continue;
+                            }
+                        };
+                        bytes_sent = 0;
+                    }
+
+                    let msg = if binary {
+                        Message::Binary(payload.clone())
+                    } else {
+                        Message::Text(String::from_utf8_lossy(&payload).into_owned())
+                    };
+
+                    let start = Instant::now();
+                    let outcome = async {
+                        socket.send(msg).await?;
+                        socket.try_next().await
+                    }
+                    .await;
+
+                    match outcome {
+                        Ok(Some(_)) => {
+                            let duration = start.elapsed().as_micros() as u64;
+                            let _ = tx.send(duration).await;
+                            bytes_sent += payload.len();
+                            success_count.fetch_add(1, Ordering::Relaxed);
+                        }
+                        _ => {
+                            failure_count.fetch_add(1, Ordering::Relaxed);
+                        }
+                    }
+                }
+
+                let _ = socket.close(None).await;
+            }
+        })
+        .await;
+
+    drop(tx);
+
+    let mut hist = Histogram::<u64>::new(3).unwrap();
+    while let Some(duration) = rx.recv().await {
+        hist.record(duration).unwrap();
+    }
+
+    let duration = start_time.elapsed();
+    let success = success_count.load(Ordering::Relaxed);
+    let failure = failure_count.load(Ordering::Relaxed);
+
+    println!("Load test completed in {:.2?}", duration);
+    println!("Successful messages: {}", success);
+    println!("Failed messages: {}", failure);
+    println!(
+        "Messages per second: {:.2}",
+        (success + failure) as f64 / duration.as_secs_f64()
+    );
+
+    println!("\nLatency Percentiles (µs):");
+    println!("P50:  {}", hist.value_at_percentile(50.0));
+    println!("P90:  {}", hist.value_at_percentile(90.0));
+    println!("P99:  {}", hist.value_at_percentile(99.0));
+    println!("Max:  {}", hist.max());
+}
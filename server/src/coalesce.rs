@@ -0,0 +1,122 @@
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use tokio::sync::watch;
+
+/// Error surfaced to callers waiting on a coalesced computation whose leader
+/// never produced a value (it panicked or was cancelled).
+#[derive(Clone, Debug)]
+pub struct CoalesceError(pub String);
+
+pub type ComputeResult = Result<u64, CoalesceError>;
+
+type Slot = watch::Receiver<Option<Arc<ComputeResult>>>;
+
+/// Which role a caller ended up in after the atomic check-and-insert: join
+/// an in-flight computation, or become its leader.
+enum Role {
+    Follower(Slot),
+    Leader(watch::Sender<Option<Arc<ComputeResult>>>, Slot),
+}
+
+/// Single-flight request coalescing keyed by `u64`. The first caller for a
+/// key becomes the leader and runs the computation; any concurrent callers
+/// for the same key await the leader's result instead of duplicating the
+/// work. If the leader panics or is cancelled before finishing, all waiters
+/// are released with a `CoalesceError` instead of hanging forever.
+#[derive(Default)]
+pub struct Coalescer {
+    inflight: Mutex<HashMap<u64, Slot>>,
+}
+
+impl Coalescer {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Coalescer::default())
+    }
+
+    /// Resolve `key`, running `compute` if no computation for it is already
+    /// in flight, or joining the in-flight one otherwise.
+    pub async fn get_or_compute<Fut>(self: &Arc<Self>, key: u64, compute: Fut) -> ComputeResult
+    where
+        Fut: Future<Output = u64> + Send + 'static,
+    {
+        // Check-and-insert must happen under a single lock acquisition: if two
+        // callers both observed a vacant entry before either inserted, they'd
+        // both become leaders and duplicate the work single-flight exists to
+        // avoid. The lock is scoped to this block, with no `.await` inside
+        // it, so the MutexGuard never ends up captured in the async fn's
+        // state machine.
+        let role = {
+            let mut inflight = self.inflight.lock().unwrap();
+            match inflight.entry(key) {
+                Entry::Occupied(entry) => Role::Follower(entry.get().clone()),
+                Entry::Vacant(entry) => {
+                    let (tx, rx) = watch::channel(None);
+                    entry.insert(rx.clone());
+                    Role::Leader(tx, rx)
+                }
+            }
+        };
+
+        match role {
+            Role::Follower(rx) => Self::join(rx).await,
+            Role::Leader(tx, rx) => {
+                let coalescer = self.clone();
+                let handle = tokio::spawn(async move {
+                    let mut guard = LeaderGuard { coalescer, key, tx, rx, completed: false };
+                    let value = compute.await;
+                    guard.complete(value)
+                });
+
+                match handle.await {
+                    Ok(result) => result,
+                    Err(_) => Err(CoalesceError(format!("leader for key {} panicked", key))),
+                }
+            }
+        }
+    }
+
+    async fn join(mut rx: Slot) -> ComputeResult {
+        if rx.wait_for(|value| value.is_some()).await.is_err() {
+            return Err(CoalesceError("leader was dropped before producing a result".into()));
+        }
+        (*rx.borrow()).clone().expect("checked Some above").as_ref().clone()
+    }
+}
+
+struct LeaderGuard {
+    coalescer: Arc<Coalescer>,
+    key: u64,
+    tx: watch::Sender<Option<Arc<ComputeResult>>>,
+    rx: Slot,
+    completed: bool,
+}
+
+impl LeaderGuard {
+    fn complete(&mut self, value: u64) -> ComputeResult {
+        self.completed = true;
+        let result: ComputeResult = Ok(value);
+        let _ = self.tx.send(Some(Arc::new(result.clone())));
+        result
+    }
+}
+
+impl Drop for LeaderGuard {
+    fn drop(&mut self) {
+        if !self.completed {
+            let err: ComputeResult = Err(CoalesceError(format!(
+                "leader for key {} was cancelled or panicked",
+                self.key
+            )));
+            let _ = self.tx.send(Some(Arc::new(err)));
+        }
+
+        // Only remove the map entry if it's still the one this guard
+        // inserted; a later leader may have already replaced it.
+        let mut inflight = self.coalescer.inflight.lock().unwrap();
+        if matches!(inflight.get(&self.key), Some(current) if current.same_channel(&self.rx)) {
+            inflight.remove(&self.key);
+        }
+    }
+}
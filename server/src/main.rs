@@ -1,26 +1,56 @@
+mod coalesce;
+mod tls;
+
 use axum::{
-    extract::{Path, Json},
+    extract::{Path, Json, State},
+    http::StatusCode,
     routing::{get, post},
     Router,
 };
+use clap::Parser;
+use coalesce::Coalescer;
 use serde::{Deserialize, Serialize};
 use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
 use std::time::Duration;
 use tokio::time::sleep;
 
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Args {
+    /// TLS certificate (PEM) to serve over HTTPS; requires --tls-key
+    #[arg(long, requires = "tls_key")]
+    tls_cert: Option<PathBuf>,
+
+    /// TLS private key (PEM) to serve over HTTPS; requires --tls-cert
+    #[arg(long, requires = "tls_cert")]
+    tls_key: Option<PathBuf>,
+}
+
 #[tokio::main]
 async fn main() {
+    let args = Args::parse();
+    let coalescer = Coalescer::new();
+
     // Build our application with a route
     let app = Router::new()
         .route("/", get(root))
         .route("/echo", post(echo))
-        .route("/delay/:seconds", get(delay));
+        .route("/delay/:seconds", get(delay))
+        .route("/compute/:n", get(compute))
+        .with_state(coalescer);
 
-    // Run it
     let addr = SocketAddr::from(([127, 0, 0, 1], 3000));
-    println!("listening on {}", addr);
-    let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
-    axum::serve(listener, app).await.unwrap();
+
+    match (args.tls_cert, args.tls_key) {
+        (Some(cert), Some(key)) => tls::serve(app, addr, &cert, &key).await,
+        _ => {
+            println!("listening on {}", addr);
+            let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
+            axum::serve(listener, app).await.unwrap();
+        }
+    }
 }
 
 // basic handler that responds with a static string
@@ -53,3 +83,16 @@ async fn delay(Path(seconds): Path<u64>) -> String {
     sleep(Duration::from_secs(seconds)).await;
     format!("Waited for {} seconds", seconds)
 }
+
+// handler that computes fib(n), coalescing concurrent requests for the same n
+// into a single computation
+async fn compute(
+    State(coalescer): State<Arc<Coalescer>>,
+    Path(n): Path<u64>,
+) -> Result<String, (StatusCode, String)> {
+    coalescer
+        .get_or_compute(n, async move { fib(n) })
+        .await
+        .map(|value| format!("fib({}) = {}", n, value))
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.0))
+}
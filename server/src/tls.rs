@@ -0,0 +1,19 @@
+use axum::Router;
+use axum_server::tls_rustls::RustlsConfig;
+use std::net::SocketAddr;
+use std::path::Path;
+
+/// Serve `app` over TLS using a PEM certificate/key pair, so the server can
+/// be benchmarked end-to-end over HTTPS alongside the load tester's TLS
+/// options.
+pub async fn serve(app: Router, addr: SocketAddr, cert_path: &Path, key_path: &Path) {
+    let config = RustlsConfig::from_pem_file(cert_path, key_path)
+        .await
+        .unwrap_or_else(|e| panic!("invalid TLS cert/key pair: {}", e));
+
+    println!("listening on {} (tls)", addr);
+    axum_server::bind_rustls(addr, config)
+        .serve(app.into_make_service())
+        .await
+        .unwrap();
+}